@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// Errors that can surface while servicing a single WebSocket message.
+/// Every variant maps to a structured error frame sent back to the client
+/// instead of panicking the actor.
+#[derive(Debug)]
+pub enum WsError {
+    RedisUnavailable,
+    KeyNotFound(String),
+    Deserialize(String),
+    UnknownPrefix(String),
+    Unauthorized(String),
+}
+
+impl WsError {
+    /// Stable machine-readable code included in the error frame.
+    pub fn code(&self) -> &'static str {
+        match self {
+            WsError::RedisUnavailable => "redis_unavailable",
+            WsError::KeyNotFound(_) => "key_not_found",
+            WsError::Deserialize(_) => "deserialize_error",
+            WsError::UnknownPrefix(_) => "unknown_prefix",
+            WsError::Unauthorized(_) => "unauthorized",
+        }
+    }
+}
+
+impl fmt::Display for WsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WsError::RedisUnavailable => write!(f, "redis connection unavailable"),
+            WsError::KeyNotFound(key) => write!(f, "key not found: {key}"),
+            WsError::Deserialize(message) => write!(f, "failed to deserialize: {message}"),
+            WsError::UnknownPrefix(prefix) => write!(f, "unknown message prefix: {prefix}"),
+            WsError::Unauthorized(reason) => write!(f, "unauthorized: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for WsError {}
+
+impl From<redis::RedisError> for WsError {
+    fn from(err: redis::RedisError) -> Self {
+        // `String` conversions fail with a type error when the key is simply
+        // absent (GET returns Nil), so surface that case distinctly
+        // from a genuinely broken connection.
+        if err.kind() == redis::ErrorKind::TypeError {
+            WsError::KeyNotFound(err.to_string())
+        } else {
+            WsError::RedisUnavailable
+        }
+    }
+}
+
+impl From<serde_json::Error> for WsError {
+    fn from(err: serde_json::Error) -> Self {
+        WsError::Deserialize(err.to_string())
+    }
+}
@@ -11,11 +11,23 @@ use actix_web::{
 };
 use actix_web_actors::ws;
 use actix_cors::Cors;
+use actix::{ Actor, Addr };
 use serde_json::json;
 use serde::Serialize;
+use std::sync::Arc;
 
 mod server;
-use self::server::MyWebSocket;
+mod rate_limiter;
+mod error;
+use self::server::{ ChatServer, GetStats, MyWebSocket, RedisPool };
+use self::rate_limiter::RateLimiter;
+
+const REDIS_URL: &str = "redis://localhost:6379";
+
+/// Default token-bucket capacity and refill rate, overridable via
+/// `RATE_LIMIT_CAPACITY` / `RATE_LIMIT_REFILL_PER_SEC`.
+const DEFAULT_RATE_LIMIT_CAPACITY: f64 = 20.0;
+const DEFAULT_RATE_LIMIT_REFILL_PER_SEC: f64 = 5.0;
 
 // define menu structure
 #[derive(Serialize)]
@@ -65,8 +77,41 @@ async fn index() -> impl Responder {
 }
 
 /// WebSocket handshake and start `MyWebSocket` actor.
-async fn echo_ws(req: HttpRequest, stream: web::Payload) -> Result<HttpResponse, Error> {
-    ws::start(MyWebSocket::new(), &req, stream)
+async fn echo_ws(
+    req: HttpRequest,
+    stream: web::Payload,
+    redis_pool: web::Data<RedisPool>,
+    chat_server: web::Data<Addr<ChatServer>>,
+    rate_limiter: web::Data<Arc<RateLimiter>>,
+    broadcast_token: web::Data<Arc<Option<String>>>
+) -> Result<HttpResponse, Error> {
+    let ip = req
+        .connection_info()
+        .peer_addr()
+        .unwrap_or("unknown")
+        .to_string();
+
+    ws::start(
+        MyWebSocket::new(
+            redis_pool.get_ref().clone(),
+            chat_server.get_ref().clone(),
+            ip,
+            REDIS_URL.to_string(),
+            rate_limiter.get_ref().clone(),
+            broadcast_token.get_ref().clone()
+        ),
+        &req,
+        stream
+    )
+}
+
+/// Report how many clients are connected right now and, per session, which
+/// IP they connected from.
+async fn stats(chat_server: web::Data<Addr<ChatServer>>) -> impl Responder {
+    match chat_server.send(GetStats).await {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
 }
 
 #[actix_web::main]
@@ -75,7 +120,37 @@ async fn main() -> std::io::Result<()> {
 
     log::info!("starting HTTP server at http://localhost:3333");
 
-    HttpServer::new(|| {
+    // Single pool shared by every `MyWebSocket` actor, bounding the total
+    // number of Redis connections regardless of how many clients connect.
+    let redis_client = redis::Client::open(REDIS_URL).expect("Failed to create Redis client");
+    let redis_pool: RedisPool = r2d2::Pool
+        ::builder()
+        .build(redis_client)
+        .expect("Failed to build Redis connection pool");
+
+    // Single registry of live sessions, shared by every worker.
+    let chat_server = ChatServer::new().start();
+
+    let rate_limit_capacity = std::env
+        ::var("RATE_LIMIT_CAPACITY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RATE_LIMIT_CAPACITY);
+    let rate_limit_refill_per_sec = std::env
+        ::var("RATE_LIMIT_REFILL_PER_SEC")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RATE_LIMIT_REFILL_PER_SEC);
+    let rate_limiter = Arc::new(RateLimiter::new(rate_limit_capacity, rate_limit_refill_per_sec));
+
+    // Unset (the default) disables the `Broadcast` op entirely.
+    let broadcast_token = Arc::new(std::env::var("BROADCAST_TOKEN").ok());
+
+    HttpServer::new(move || {
+        let redis_pool = redis_pool.clone();
+        let chat_server = chat_server.clone();
+        let rate_limiter = rate_limiter.clone();
+        let broadcast_token = broadcast_token.clone();
         let cors = Cors::default()
             .allowed_origin("http://localhost:5173") // Allow specific origin
             .allowed_methods(vec!["GET", "POST", "OPTION"]) // Allow specific methods
@@ -92,10 +167,15 @@ async fn main() -> std::io::Result<()> {
         // of the same type within that time frame, improving performance.
 
         App::new()
+            .app_data(web::Data::new(redis_pool))
+            .app_data(web::Data::new(chat_server))
+            .app_data(web::Data::new(rate_limiter))
+            .app_data(web::Data::new(broadcast_token))
             .wrap(cors)
             // http routes
             .service(web::resource("/").to(index))
             .service(web::resource("/menu").to(menu))
+            .service(web::resource("/stats").to(stats))
             // websocket route
             .service(web::resource("/ws").route(web::get().to(echo_ws)))
             // enable logger
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{ Duration, Instant };
+
+/// Idle buckets (refilled back to capacity and untouched this long) are
+/// evicted so clients that connect once don't accumulate in the map forever.
+const IDLE_EVICTION_THRESHOLD: Duration = Duration::from_secs(300);
+
+/// How often `check` opportunistically sweeps for idle buckets.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Token-bucket rate limiter keyed by client IP, modeled on Lemmy's
+/// `RateLimitCell`. Each IP accrues tokens over time up to `capacity` and
+/// spends one per message; once its bucket is empty, messages are dropped
+/// instead of reaching Redis, so one noisy client can't starve the rest.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_rate: f64,
+    buckets: Mutex<HashMap<IpAddr, (f64, Instant)>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: f64, refill_rate: f64) -> Self {
+        Self {
+            capacity,
+            refill_rate,
+            buckets: Mutex::new(HashMap::new()),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Refills `ip`'s bucket for the time elapsed since its last check, then
+    /// spends a token if one is available. Returns `true` if the message
+    /// should proceed, `false` if it should be dropped.
+    pub fn check(&self, ip: IpAddr) -> bool {
+        let mut buckets = self.buckets.lock().expect("rate limiter mutex poisoned");
+        let now = Instant::now();
+        let (tokens, last_refill) = buckets.entry(ip).or_insert((self.capacity, now));
+
+        let elapsed = now.duration_since(*last_refill).as_secs_f64();
+        *tokens = (*tokens + elapsed * self.refill_rate).min(self.capacity);
+        *last_refill = now;
+
+        let allowed = if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        };
+
+        self.evict_stale(&mut buckets, now);
+        allowed
+    }
+
+    /// Drop buckets that have sat full and untouched for
+    /// `IDLE_EVICTION_THRESHOLD`, bounding memory use under an IP-flood of
+    /// distinct addresses. Runs at most once per `SWEEP_INTERVAL`.
+    fn evict_stale(&self, buckets: &mut HashMap<IpAddr, (f64, Instant)>, now: Instant) {
+        let mut last_sweep = self.last_sweep.lock().expect("rate limiter mutex poisoned");
+        if now.duration_since(*last_sweep) < SWEEP_INTERVAL {
+            return;
+        }
+        *last_sweep = now;
+
+        buckets.retain(|_, (tokens, last_refill)| {
+            let idle = now.duration_since(*last_refill);
+            let refilled = (*tokens + idle.as_secs_f64() * self.refill_rate).min(self.capacity);
+            refilled < self.capacity || idle < IDLE_EVICTION_THRESHOLD
+        });
+    }
+}
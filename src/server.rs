@@ -1,3 +1,8 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, AtomicUsize, Ordering };
+use std::thread;
 use std::time::{ Duration, Instant };
 use redis::Commands;
 use actix::prelude::*;
@@ -5,19 +10,191 @@ use actix_web_actors::ws;
 use serde_json;
 use serde::{ Serialize, Deserialize };
 
+use crate::error::WsError;
+use crate::rate_limiter::RateLimiter;
+
 /// How often heartbeat pings are sent
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 
 /// How long before lack of client response causes a timeout
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Consecutive rate-limit violations before a socket is dropped outright.
+const RATE_LIMIT_MAX_VIOLATIONS: u32 = 10;
+
+/// Redis keyspace-notification channel prefix for db 0 (`notify-keyspace-events KEA`).
+const KEYSPACE_PREFIX: &str = "__keyspace@0__:";
+
+/// Shared pool of Redis connections, handed out to each `MyWebSocket` actor
+/// instead of every socket opening its own connection.
+///
+/// One connection per actor keeps actors isolated but doesn't scale: Redis
+/// nodes only allow up to 10,000 simultaneous connections (or 4 per megabyte
+/// of memory, whichever is larger), so 10k clients would exhaust it on its
+/// own. Sharing a single connection avoids that ceiling but turns it into a
+/// synchronization bottleneck under concurrency. A bounded pool splits the
+/// difference: each message checks out a connection for as long as it needs
+/// it, and the pool caps how many are ever open at once.
+pub type RedisPool = r2d2::Pool<redis::Client>;
+
+/// A text frame pushed from `ChatServer` down to a single session.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct WsMessage(pub String);
+
+/// Sent by a `MyWebSocket` on `started` to register itself with `ChatServer`.
+/// The response is the session id the server assigned it.
+#[derive(Message)]
+#[rtype(result = "usize")]
+pub struct Connect {
+    pub addr: Recipient<WsMessage>,
+    pub ip: String,
+}
+
+/// Sent by a `MyWebSocket` on `stopping` to drop its session.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Disconnect {
+    pub id: usize,
+}
+
+/// Push `message` out to every currently connected session.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Broadcast {
+    pub message: String,
+}
+
+/// Snapshot of connection state, returned by `/stats`.
+#[derive(Message)]
+#[rtype(result = "Stats")]
+pub struct GetStats;
+
+/// One keyspace-notification event relayed from a subscription thread into
+/// the owning `MyWebSocket` actor.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PushUpdate {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize)]
+pub struct SessionInfo {
+    pub id: usize,
+    pub ip: String,
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    pub connection_count: usize,
+    pub sessions: Vec<SessionInfo>,
+}
+
+/// Central registry of live `MyWebSocket` sessions. Unlike `MyWebSocket`,
+/// which is one actor per connection, there is a single `ChatServer`
+/// instance shared across the whole process (via `web::Data<Addr<ChatServer>>`),
+/// so it can track who's connected and broadcast to all of them.
+pub struct ChatServer {
+    sessions: HashMap<usize, Recipient<WsMessage>>,
+    ips: HashMap<usize, String>,
+    /// Monotonic source of session ids; random ids can collide and silently
+    /// overwrite another session's entry.
+    next_id: AtomicUsize,
+}
+
+impl ChatServer {
+    pub fn new() -> Self {
+        Self { sessions: HashMap::new(), ips: HashMap::new(), next_id: AtomicUsize::new(1) }
+    }
+}
+
+impl Default for ChatServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Actor for ChatServer {
+    type Context = Context<Self>;
+}
+
+impl Handler<Connect> for ChatServer {
+    type Result = usize;
+
+    fn handle(&mut self, msg: Connect, _: &mut Self::Context) -> Self::Result {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.sessions.insert(id, msg.addr);
+        self.ips.insert(id, msg.ip);
+        id
+    }
+}
+
+impl Handler<Disconnect> for ChatServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: Disconnect, _: &mut Self::Context) {
+        self.sessions.remove(&msg.id);
+        self.ips.remove(&msg.id);
+    }
+}
+
+impl Handler<Broadcast> for ChatServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: Broadcast, _: &mut Self::Context) {
+        for recipient in self.sessions.values() {
+            recipient.do_send(WsMessage(msg.message.clone()));
+        }
+    }
+}
+
+impl Handler<GetStats> for ChatServer {
+    type Result = MessageResult<GetStats>;
+
+    fn handle(&mut self, _: GetStats, _: &mut Self::Context) -> Self::Result {
+        let sessions = self.sessions
+            .keys()
+            .map(|id| SessionInfo {
+                id: *id,
+                ip: self.ips.get(id).cloned().unwrap_or_else(|| "unknown".to_string()),
+            })
+            .collect::<Vec<_>>();
+
+        MessageResult(Stats { connection_count: sessions.len(), sessions })
+    }
+}
+
 /// websocket connection is long running connection, it easier
 /// to handle with an actor
 pub struct MyWebSocket {
     /// Client must send ping at least once per 10 seconds (CLIENT_TIMEOUT),
     /// otherwise we drop connection.
     hb: Instant,
-    redis_con: Option<redis::Connection>,
+    /// Pool shared across all sockets; a connection is checked out per
+    /// message rather than held for the lifetime of the actor.
+    redis_pool: RedisPool,
+    /// Address of the shared session registry.
+    chat_server: Addr<ChatServer>,
+    /// Session id assigned by `ChatServer` once `Connect` completes.
+    id: usize,
+    /// Peer IP captured at handshake time, reported back via `Connect`.
+    ip: String,
+    /// Redis connection string, used to open a dedicated pubsub connection
+    /// per subscription (pooled connections are for one-shot commands only).
+    redis_url: String,
+    /// Active `Subscribe` subscriptions for this socket, keyed by key pattern.
+    /// Each one owns a background thread; the flag tells it to stop.
+    subscriptions: HashMap<String, Arc<AtomicBool>>,
+    /// Peer IP parsed for the rate limiter; falls back to unspecified if the
+    /// handshake didn't yield a parseable address.
+    ip_addr: IpAddr,
+    /// Shared token-bucket limiter, keyed by `ip_addr`.
+    rate_limiter: Arc<RateLimiter>,
+    /// Consecutive messages dropped for this socket by the rate limiter.
+    rate_limit_violations: u32,
+    /// Shared secret required on a `Broadcast` op; `None` disables it entirely.
+    broadcast_token: Arc<Option<String>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,9 +204,86 @@ struct AnimationMetadata {
     text: Option<String>,
 }
 
+/// Incoming requests, dispatched on the `op` field instead of scanning the
+/// raw text for a `prefix:` delimiter.
+#[derive(Deserialize)]
+#[serde(tag = "op")]
+enum ClientMsg {
+    GetAnimation {
+        key: String,
+    },
+    GetAnimationQueue {
+        key: String,
+    },
+    Subscribe {
+        pattern: String,
+    },
+    /// Fan a message out to every connected client. Gated behind
+    /// `BROADCAST_TOKEN` so an arbitrary client can't use this for
+    /// unauthenticated broadcast amplification.
+    Broadcast {
+        token: String,
+        message: String,
+    },
+}
+
+/// Outgoing responses/pushes, replacing the old `key::payload` concatenation.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMsg {
+    Animation {
+        key: String,
+        data: String,
+    },
+    AnimationQueue {
+        key: String,
+        items: Vec<AnimationMetadata>,
+    },
+    Update {
+        key: String,
+        value: String,
+    },
+    Broadcast {
+        message: String,
+    },
+    Error {
+        code: String,
+        message: String,
+    },
+}
+
 impl MyWebSocket {
-    pub fn new() -> Self {
-        Self { hb: Instant::now(), redis_con: None }
+    pub fn new(
+        redis_pool: RedisPool,
+        chat_server: Addr<ChatServer>,
+        ip: String,
+        redis_url: String,
+        rate_limiter: Arc<RateLimiter>,
+        broadcast_token: Arc<Option<String>>
+    ) -> Self {
+        let ip_addr = match ip.parse() {
+            Ok(ip_addr) => ip_addr,
+            Err(_) => {
+                // Every unparseable IP collapses into one shared bucket, so
+                // make that failure mode observable instead of silent.
+                println!("Failed to parse client IP {ip:?}, rate-limiting it under a shared 0.0.0.0 bucket");
+                IpAddr::from([0, 0, 0, 0])
+            }
+        };
+
+        Self {
+            hb: Instant::now(),
+            redis_pool,
+            chat_server,
+            id: 0,
+            ip,
+            redis_url,
+            subscriptions: HashMap::new(),
+            ip_addr,
+            rate_limiter,
+            rate_limit_violations: 0,
+            broadcast_token,
+        }
     }
 
     /// helper method that sends ping to client every 5 seconds (HEARTBEAT_INTERVAL).
@@ -55,53 +309,231 @@ impl MyWebSocket {
             ctx.ping(b"");
         });
     }
-}
 
-///
-// One connection per actor:
+    /// Start streaming keyspace notifications for `pattern` to this socket.
+    ///
+    /// `redis::Connection` pubsub is blocking, so the subscription runs on
+    /// its own thread with its own connection and relays every change back
+    /// into this actor via `PushUpdate`. A no-op if already subscribed.
+    fn subscribe(&mut self, ctx: &mut <Self as Actor>::Context, pattern: String) {
+        if self.subscriptions.contains_key(&pattern) {
+            return;
+        }
 
-// Pros:
+        let stop = Arc::new(AtomicBool::new(false));
+        self.subscriptions.insert(pattern.clone(), stop.clone());
 
-// Isolation: Each actor has its own dedicated connection, ensuring isolation and avoiding potential race conditions or interference.
-// Simplicity: Easier to manage and debug as connections are not shared.
-// Scalability: Can handle a higher number of concurrent actors as each has its own connection.
+        let redis_url = self.redis_url.clone();
+        let redis_pool = self.redis_pool.clone();
+        let channel_pattern = format!("{KEYSPACE_PREFIX}{pattern}");
+        let addr = ctx.address();
 
-// Cons:
+        thread::spawn(move || {
+            let client = match redis::Client::open(redis_url.as_str()) {
+                Ok(client) => client,
+                Err(err) => {
+                    println!("Failed to open Redis client for subscription: {err}");
+                    return;
+                }
+            };
+            let mut con = match client.get_connection() {
+                Ok(con) => con,
+                Err(err) => {
+                    println!("Failed to open pubsub connection: {err}");
+                    return;
+                }
+            };
+
+            if
+                let Err(err) = redis
+                    ::cmd("CONFIG")
+                    .arg("SET")
+                    .arg("notify-keyspace-events")
+                    .arg("KEA")
+                    .query::<()>(&mut con)
+            {
+                println!("Failed to enable keyspace notifications: {err}");
+                return;
+            }
 
-// Resource usage: Creates more connections, leading to higher memory and CPU consumption on the Redis server and your application.
-// Connection overhead: Establishing and maintaining multiple connections can add overhead, impacting performance.
-// Single connection for all actors:
+            let mut pubsub = con.as_pubsub();
+            if let Err(err) = pubsub.psubscribe(&channel_pattern) {
+                println!("Failed to psubscribe to {channel_pattern}: {err}");
+                return;
+            }
+            // Keep get_message() from blocking forever so the stop flag gets checked.
+            let _ = pubsub.set_read_timeout(Some(Duration::from_millis(500)));
+
+            while !stop.load(Ordering::Relaxed) {
+                match pubsub.get_message() {
+                    Ok(message) => {
+                        let channel = message.get_channel_name();
+                        let key = channel.strip_prefix(KEYSPACE_PREFIX).unwrap_or(channel).to_string();
+                        // The keyspace payload is the *event name* (`set`, `lpush`,
+                        // `del`, ...), not the key's new value, so fetch the value
+                        // ourselves on a separate connection (pubsub connections
+                        // can't also issue commands).
+                        let event: String = message.get_payload().unwrap_or_default();
+
+                        if event == "del" || event == "expired" {
+                            addr.do_send(PushUpdate { key, value: String::new() });
+                            continue;
+                        }
 
-// Pros:
+                        match redis_pool.get() {
+                            Ok(mut con) => {
+                                match MyWebSocket::fetch_current_value(&mut con, &key) {
+                                    Ok(value) => addr.do_send(PushUpdate { key, value }),
+                                    Err(err) => println!("Failed to fetch updated value for {key}: {err}"),
+                                }
+                            }
+                            Err(err) =>
+                                println!("Failed to check out Redis connection for {key}: {err}"),
+                        }
+                    }
+                    Err(err) if err.is_timeout() => {
+                        continue;
+                    }
+                    Err(err) => {
+                        println!("pubsub error on {channel_pattern}: {err}");
+                        break;
+                    }
+                }
+            }
+        });
+    }
 
-// Resource efficiency: Uses only one connection, reducing memory and CPU overhead.
-// Lower connection overhead: Less time spent establishing and maintaining connections.
+    /// Stop every active subscription thread owned by this socket.
+    fn unsubscribe_all(&mut self) {
+        for stop in self.subscriptions.values() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        self.subscriptions.clear();
+    }
 
-// Cons:
+    /// Serialize and send a `ServerMsg`. Serialization can't meaningfully
+    /// fail for these variants, but we still log rather than panic.
+    fn send_msg(ctx: &mut <Self as Actor>::Context, msg: &ServerMsg) {
+        match serde_json::to_string(msg) {
+            Ok(text) => ctx.text(text),
+            Err(err) => println!("Failed to serialize ServerMsg: {err}"),
+        }
+    }
 
-// Complexity: Sharing a connection requires careful synchronization and error handling to avoid conflicts and race conditions.
-// Scalability limitations: Might reach the maximum allowed connections on the Redis server if the number of actors grows significantly.
-// Performance bottlenecks: Shared connections could become bottlenecks during high concurrency, impacting performance for all actors.
+    /// Send a structured `{"type":"error",...}` frame instead of panicking
+    /// the actor, keeping the connection alive after a failed request.
+    fn send_error(ctx: &mut <Self as Actor>::Context, err: &WsError) {
+        println!("WS error: {err}");
+        Self::send_msg(ctx, &ServerMsg::Error { code: err.code().to_string(), message: err.to_string() });
+    }
 
-// Redis nodes can have up to either 10,000 simultaneous connections
-// or 4 simultaneous connections per megabyte of memory, whichever is larger.
-///
+    /// Check out a pooled Redis connection, sending an error frame on failure.
+    fn checkout_redis(
+        &self,
+        ctx: &mut <Self as Actor>::Context
+    ) -> Option<r2d2::PooledConnection<redis::Client>> {
+        match self.redis_pool.get() {
+            Ok(con) => Some(con),
+            Err(err) => {
+                println!("Failed to check out Redis connection from pool: {err}");
+                Self::send_error(ctx, &WsError::RedisUnavailable);
+                None
+            }
+        }
+    }
+
+    /// Fetch a single animation's raw payload by key.
+    fn fetch_animation(
+        con: &mut r2d2::PooledConnection<redis::Client>,
+        key: &str
+    ) -> Result<String, WsError> {
+        let value: String = con.get(key)?;
+        Ok(value)
+    }
+
+    /// Fetch an animation queue by key.
+    fn fetch_animation_queue(
+        con: &mut r2d2::PooledConnection<redis::Client>,
+        key: &str
+    ) -> Result<Vec<AnimationMetadata>, WsError> {
+        let raw: Vec<String> = con.lrange(key, 0, -1)?;
+        // iterate over the list, convert string item to json object
+        let values: Vec<AnimationMetadata> = raw
+            .iter()
+            .map(|json_string| serde_json::from_str(json_string))
+            .collect::<Result<_, _>>()?;
+
+        // todo get each animation's full data from redis on the client side,
+        // to reduce the size of data sent over the network
+        Ok(values)
+    }
+
+    /// Fetch a key's current payload for the subscription stream, after a
+    /// keyspace notification fires. The notification itself only carries the
+    /// event name, so `key` may be a string or a list depending on which
+    /// command triggered it.
+    fn fetch_current_value(
+        con: &mut r2d2::PooledConnection<redis::Client>,
+        key: &str
+    ) -> Result<String, WsError> {
+        match con.get::<&str, String>(key) {
+            Ok(value) => Ok(value),
+            // GET on a list key is rejected by the server with WRONGTYPE.
+            Err(err) if err.code() == Some("WRONGTYPE") => {
+                let items: Vec<String> = con.lrange(key, 0, -1)?;
+                Ok(serde_json::to_string(&items)?)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
 
 impl Actor for MyWebSocket {
     type Context = ws::WebsocketContext<Self>;
 
-    /// Method is called on actor start. We start the heartbeat process here.
+    /// Method is called on actor start. We start the heartbeat process here
+    /// and register with `ChatServer` to get a session id.
     fn started(&mut self, ctx: &mut Self::Context) {
         self.hb(ctx);
 
-        // Establish Redis connection here
-        let client = redis::Client
-            ::open("redis://localhost:6379")
-            .expect("Failed to connect to Redis");
-        let con = client.get_connection().expect("Failed to get Redis connection");
+        let addr = ctx.address();
+        self.chat_server
+            .send(Connect { addr: addr.recipient(), ip: self.ip.clone() })
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(id) => act.id = id,
+                    _ => ctx.stop(),
+                }
+                fut::ready(())
+            })
+            .wait(ctx);
+    }
+
+    /// Drop our session from `ChatServer` and stop any subscription threads
+    /// once the socket is closing.
+    fn stopping(&mut self, _ctx: &mut Self::Context) -> Running {
+        self.chat_server.do_send(Disconnect { id: self.id });
+        self.unsubscribe_all();
+        Running::Stop
+    }
+}
+
+/// Relay a push from `ChatServer` (e.g. a `Broadcast`) down to this client.
+impl Handler<WsMessage> for MyWebSocket {
+    type Result = ();
 
-        // Store the connection in a field for later use
-        self.redis_con = Some(con);
+    fn handle(&mut self, msg: WsMessage, ctx: &mut Self::Context) {
+        ctx.text(msg.0);
+    }
+}
+
+/// Relay a keyspace-notification event from a subscription thread.
+impl Handler<PushUpdate> for MyWebSocket {
+    type Result = ();
+
+    fn handle(&mut self, msg: PushUpdate, ctx: &mut Self::Context) {
+        Self::send_msg(ctx, &ServerMsg::Update { key: msg.key, value: msg.value });
     }
 }
 
@@ -120,87 +552,81 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for MyWebSocket {
                 self.hb = Instant::now();
             }
             Ok(ws::Message::Text(text)) => {
-                // Access the Redis connection
-                if let Some(con) = &mut self.redis_con {
-                    // Access and use each part here
-                    if text.contains(":") {
-                        let delimiter_index = text.find(":").expect("Failed to find delimiter");
-
-                        let prefix = &text[..delimiter_index + 1]; // includes delimiters
-                        // let suffix = &text[delimiter_index + 1..];
-
-                        // covert text from bytestring::ByteString to &str
-                        let reids_key = text.as_ref();
-
-                        match prefix {
-                            "am:" => {
-                                let value: String = con
-                                    .get::<&str, String>(&reids_key)
-                                    .expect("Failed to read data from Redis");
-
-                                println!(
-                                    "fetched data from redis, size {}",
-                                    value.as_bytes().len()
-                                );
-
-                                // Concatenation here:
-                                let message = format!("{}::{}", reids_key, value);
-
-                                let msg_len = message.as_bytes().len();
-
-                                ctx.text(message); // Send the concatenated message
-
-                                println!("send messahe to client, size {}", msg_len);
+                if !self.rate_limiter.check(self.ip_addr) {
+                    self.rate_limit_violations += 1;
+                    Self::send_msg(ctx, &ServerMsg::Error {
+                        code: "rate_limited".to_string(),
+                        message: "rate limited, slow down".to_string(),
+                    });
+
+                    if self.rate_limit_violations >= RATE_LIMIT_MAX_VIOLATIONS {
+                        println!("{} exceeded rate limit too many times, disconnecting", self.ip);
+                        ctx.stop();
+                    }
+                    return;
+                }
+                self.rate_limit_violations = 0;
+
+                let client_msg: ClientMsg = match serde_json::from_str(&text) {
+                    Ok(client_msg) => client_msg,
+                    Err(err) => {
+                        // serde's own "unknown variant" message already names the
+                        // rejected `op`, so surface it as `UnknownPrefix` without
+                        // keeping a second, separately-maintained list of variants.
+                        if err.to_string().contains("unknown variant") {
+                            Self::send_error(ctx, &WsError::UnknownPrefix(err.to_string()));
+                        } else {
+                            Self::send_error(ctx, &WsError::from(err));
+                        }
+                        return;
+                    }
+                };
+
+                match client_msg {
+                    ClientMsg::GetAnimation { key } => {
+                        if let Some(mut con) = self.checkout_redis(ctx) {
+                            match Self::fetch_animation(&mut con, &key) {
+                                Ok(data) => Self::send_msg(ctx, &ServerMsg::Animation { key, data }),
+                                Err(err) => Self::send_error(ctx, &err),
                             }
-                            "amq:" => {
-                                let value: Vec<String> = con
-                                    .lrange(&reids_key, 0, -1)
-                                    .expect("Failed to read list from Redis");
-
-                                // iterate over the list, convert string item to json object
-                                let values: Vec<AnimationMetadata> = value
-                                    .iter()
-                                    .map(|json_string|
-                                        serde_json
-                                            ::from_str(json_string)
-                                            .expect("Failed to parse json string")
-                                    )
-                                    .collect();
-
-                                println!("list size {}", values.len());
-
-                                // Concatenation here:
-                                let message = format!(
-                                    "{}::{}",
-                                    reids_key,
-                                    serde_json
-                                        ::to_string(&values)
-                                        .expect("Failed to serialize list to string")
-                                );
-
-                                let msg_len = message.as_bytes().len();
-
-                                ctx.text(message); // Send the concatenated message
-
-                                // todo iterate over list, and get each animation data from redis
-                                // but do this on the client side, to reduce the size of data sent over the network
-
-                                println!("send messahe to client, size {}", msg_len);
+                        }
+                    }
+                    ClientMsg::GetAnimationQueue { key } => {
+                        if let Some(mut con) = self.checkout_redis(ctx) {
+                            match Self::fetch_animation_queue(&mut con, &key) {
+                                Ok(items) =>
+                                    Self::send_msg(ctx, &ServerMsg::AnimationQueue { key, items }),
+                                Err(err) => Self::send_error(ctx, &err),
                             }
-                            _ => {
-                                println!("received unknown text {}", text);
+                        }
+                    }
+                    ClientMsg::Subscribe { pattern } => {
+                        // Stream every future change to keys matching this pattern.
+                        self.subscribe(ctx, pattern);
+                    }
+                    ClientMsg::Broadcast { token, message } => {
+                        // Only a holder of the shared secret may fan a message
+                        // out to every connected client; otherwise any socket
+                        // could use this for broadcast amplification.
+                        match self.broadcast_token.as_deref() {
+                            Some(expected) if expected == token => {
+                                match serde_json::to_string(&ServerMsg::Broadcast { message }) {
+                                    Ok(frame) => self.chat_server.do_send(Broadcast { message: frame }),
+                                    Err(err) => Self::send_error(ctx, &WsError::from(err)),
+                                }
                             }
+                            _ =>
+                                Self::send_error(
+                                    ctx,
+                                    &WsError::Unauthorized("invalid broadcast token".to_string())
+                                ),
                         }
-                    } else {
-                        println!("received unknown text {}", text);
                     }
-                } else {
-                    // Handle the case where the connection is not established
-                    println!("Redis connection not available");
                 }
             }
             Ok(ws::Message::Binary(bin)) => ctx.binary(bin),
             Ok(ws::Message::Close(reason)) => {
+                self.unsubscribe_all();
                 ctx.close(reason);
                 ctx.stop();
             }